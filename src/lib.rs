@@ -1,11 +1,10 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::mpsc;
 use std::{error::Error, fs::File};
-use std::{fmt, io::Stdout};
-use std::{
-    fs::OpenOptions,
-    io::{prelude::*, BufReader},
-    path::Path,
-};
+use std::fmt;
+use std::{fs::OpenOptions, io::prelude::*, path::Path};
 
 use clipboard::{ClipboardContext, ClipboardProvider};
 use crossbeam::thread;
@@ -19,8 +18,6 @@ use termion::{event::Key, raw::RawTerminal};
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 
-use itertools::Itertools;
-
 #[derive(Debug, Clone)]
 enum Errors {
     ParseCommandError = 1,
@@ -37,18 +34,45 @@ impl fmt::Display for Errors {
 
 impl Error for Errors {}
 
+// Where a suggestion came from: parsed out of local shell history, or fetched live from an
+// online source like cheat.sh.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CommandSource {
+    Local,
+    Remote,
+}
+
 #[derive(Debug, Clone)]
 pub struct Command {
     id: u32,
     command: String,
+    source: CommandSource,
 }
 
 impl Command {
     pub fn new(id: u32, command: String) -> Command {
-        Command { id, command }
+        Command {
+            id,
+            command,
+            source: CommandSource::Local,
+        }
+    }
+
+    pub fn new_remote(command: String) -> Command {
+        Command {
+            id: 0,
+            command,
+            source: CommandSource::Remote,
+        }
     }
 
     pub fn from_string(s: &str) -> Result<Command, Box<dyn Error>> {
+        Command::from_zsh_extended(s)
+    }
+
+    // Parses a single zsh EXTENDED_HISTORY entry, e.g. `: 1600000000:0;cmd`.
+    // The entry may already have continuation lines appended, each followed by `\r\n`.
+    fn from_zsh_extended(s: &str) -> Result<Command, Box<dyn Error>> {
         // Regex version is so slow
         // let re = Regex::new(r": (\d{10}):\d;([\s\S]*)").unwrap();
         // let captures: Vec<regex::Captures> = re.captures_iter(s).collect();
@@ -66,12 +90,15 @@ impl Command {
         Ok(Command::new(id.parse::<u32>()?, String::from(cmd.trim())))
     }
 
+    // Score returned by `get_match_score` when the query does not fuzzy-match at all.
+    const NO_MATCH_SCORE: i64 = -1508;
+
     pub fn get_match_score(&self, s: &String) -> i64 {
         let matcher = SkimMatcherV2::default();
         let score = matcher
             .fuzzy_indices(&self.command, s)
             .map(|(score, _)| score)
-            .unwrap_or(-1508);
+            .unwrap_or(Command::NO_MATCH_SCORE);
         return score;
 
         // let mut query = s.chars();
@@ -106,12 +133,160 @@ impl Command {
     }
 }
 
+// The on-disk layout of a shell history file. Each variant knows how to split its
+// file's content into individual `Command`s.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum HistoryFormat {
+    ZshExtended,
+    BashPlain,
+    BashWithTimestamp,
+    Fish,
+}
+
+impl HistoryFormat {
+    fn detect(path: &Path, content: &str) -> HistoryFormat {
+        if path.to_string_lossy().contains("fish_history") {
+            return HistoryFormat::Fish;
+        }
+        match content.lines().find(|line| !line.trim().is_empty()) {
+            Some(line) if line.starts_with(": ") => HistoryFormat::ZshExtended,
+            Some(line) if line.starts_with("- cmd:") => HistoryFormat::Fish,
+            _ => {
+                let has_timestamps = content.lines().any(|line| {
+                    line.len() > 1
+                        && line.starts_with('#')
+                        && line[1..].trim().chars().all(|c| c.is_ascii_digit())
+                });
+                if has_timestamps {
+                    HistoryFormat::BashWithTimestamp
+                } else {
+                    HistoryFormat::BashPlain
+                }
+            }
+        }
+    }
+
+    fn parse(&self, content: &str) -> Vec<Command> {
+        match self {
+            HistoryFormat::ZshExtended => HistoryFormat::parse_zsh_extended(content),
+            HistoryFormat::BashPlain => HistoryFormat::parse_bash_plain(content),
+            HistoryFormat::BashWithTimestamp => HistoryFormat::parse_bash_with_timestamp(content),
+            HistoryFormat::Fish => HistoryFormat::parse_fish(content),
+        }
+    }
+
+    fn parse_zsh_extended(content: &str) -> Vec<Command> {
+        let mut commands_str: Vec<String> = vec![];
+        let mut cur_command = String::from("");
+        content.lines().for_each(|line| {
+            let first_char = line.chars().nth(0).unwrap_or('?');
+            if first_char == ':' {
+                if !cur_command.is_empty() {
+                    commands_str.push(cur_command.clone());
+                }
+                cur_command = String::from(line);
+            } else {
+                cur_command.push_str(&format!("{}\r\n", line));
+            };
+        });
+        if !cur_command.is_empty() {
+            commands_str.push(cur_command);
+        }
+        commands_str
+            .iter()
+            .filter_map(|cmd_str| Command::from_zsh_extended(cmd_str).ok())
+            .collect()
+    }
+
+    // Plain bash history: one command per line, no timestamp.
+    fn parse_bash_plain(content: &str) -> Vec<Command> {
+        content
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| Command::new(0, String::from(line)))
+            .collect()
+    }
+
+    // Bash history with `HISTTIMEFORMAT` set: a `#<epoch>` comment line precedes each command.
+    fn parse_bash_with_timestamp(content: &str) -> Vec<Command> {
+        let mut commands = vec![];
+        let mut pending_ts: Option<u32> = None;
+        for line in content.lines() {
+            if let Some(ts_str) = line.strip_prefix('#') {
+                if let Ok(ts) = ts_str.trim().parse::<u32>() {
+                    pending_ts = Some(ts);
+                    continue;
+                }
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            commands.push(Command::new(
+                pending_ts.take().unwrap_or(0),
+                String::from(line.trim()),
+            ));
+        }
+        commands
+    }
+
+    // Fish's YAML-ish history: `- cmd: ...` blocks optionally followed by a `when: <epoch>` line.
+    fn parse_fish(content: &str) -> Vec<Command> {
+        let mut commands = vec![];
+        let mut cur_command: Option<String> = None;
+        let mut cur_ts: u32 = 0;
+        for line in content.lines() {
+            let trimmed = line.trim_start();
+            if let Some(cmd) = trimmed.strip_prefix("- cmd:") {
+                if let Some(prev) = cur_command.take() {
+                    commands.push(Command::new(cur_ts, prev));
+                }
+                cur_command = Some(String::from(cmd.trim()));
+                cur_ts = 0;
+            } else if let Some(when) = trimmed.strip_prefix("when:") {
+                cur_ts = when.trim().parse::<u32>().unwrap_or(0);
+            }
+        }
+        if let Some(prev) = cur_command.take() {
+            commands.push(Command::new(cur_ts, prev));
+        }
+        commands
+    }
+}
+
 #[derive(Debug)]
 pub struct Finder {
     commands: Vec<Command>,
     query: String,
 }
 
+// Options that control where history is read from and how a chosen command is surfaced,
+// threaded through from the CLI down into `Finder::render` and the output helpers.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub history_file: Option<PathBuf>,
+    pub limit: usize,
+    pub use_clipboard: bool,
+    pub print_to_stdout: bool,
+}
+
+impl Config {
+    pub fn new() -> Config {
+        Config {
+            history_file: None,
+            limit: Finder::DEFAULT_NUM_SUGGESTIONS,
+            use_clipboard: true,
+            print_to_stdout: false,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config::new()
+    }
+}
+
 impl Finder {
     pub fn new(commands: Vec<Command>, query: String) -> Finder {
         Finder { commands, query }
@@ -121,76 +296,71 @@ impl Finder {
         Finder::new(commands, String::from(""))
     }
 
-    pub fn new_with_bash_history() -> Result<Finder, Box<dyn Error>> {
-        let paths = Finder::get_history_file_path();
+    pub fn new_with_bash_history(config: &Config) -> Result<Finder, Box<dyn Error>> {
+        let paths = Finder::get_history_file_path(config);
         let mut all_commands: Vec<Command> = vec![];
         for path in paths {
-            let f_res = File::open(&path);
-            if f_res.is_err() {
+            let mut f = match File::open(&path) {
+                Ok(f) => f,
+                Err(_) => continue,
+            };
+            let mut content = String::from("");
+            if f.read_to_string(&mut content).is_err() {
                 continue;
             }
-            let f = f_res?;
-            let buf_reader = BufReader::new(f);
-            let lines: Vec<String> = buf_reader.lines().filter_map(|line| line.ok()).collect();
-            let mut commands_str: Vec<String> = vec![];
-            let mut cur_command = String::from("");
-            lines.iter().for_each(|line| {
-                let first_char = line.chars().nth(0).unwrap_or('?');
-                if first_char == ':' {
-                    commands_str.push(cur_command.clone());
-                    cur_command = String::from(line);
-                } else {
-                    cur_command.push_str(&format!("{}\r\n", line));
-                };
-            });
-            if !cur_command.is_empty() {
-                commands_str.push(cur_command);
-            }
-            let mut commands: Vec<Command> = commands_str
-                .iter()
-                .filter_map(|cmd_str| match Command::from_string(cmd_str) {
-                    Ok(cmd) => Some(cmd),
-                    Err(_) => None,
-                })
-                .collect();
-            all_commands.append(&mut commands);
+            let format = HistoryFormat::detect(&path, &content);
+            all_commands.append(&mut format.parse(&content));
         }
         Ok(Finder::new_without_query(all_commands))
     }
 
-    fn get_history_file_path() -> Vec<PathBuf> {
-        let res = if let Ok(hist_file) = std::env::var("HISTFILE") {
-            vec![PathBuf::from(hist_file)]
-        } else {
-            if let Ok(shell_path) = std::env::var("SHELL") {
-                if shell_path.contains("zsh") {
-                    if let Ok(home_path) = std::env::var("HOME") {
-                        vec![
-                            PathBuf::from(format!("{}/.zhistory", home_path)),
-                            PathBuf::from(format!("{}/.zsh_history", home_path)),
-                        ]
-                    } else {
-                        vec![]
-                    }
-                } else {
-                    // Only supported zsh
-                    vec![]
-                }
-            } else {
-                vec![]
-            }
+    fn get_history_file_path(config: &Config) -> Vec<PathBuf> {
+        if let Some(history_file) = &config.history_file {
+            return vec![history_file.clone()];
+        }
+        if let Ok(hist_file) = std::env::var("HISTFILE") {
+            return vec![PathBuf::from(hist_file)];
+        }
+        let home_path = match std::env::var("HOME") {
+            Ok(home_path) => home_path,
+            Err(_) => return vec![],
         };
-        res
+        let shell_path = std::env::var("SHELL").unwrap_or_default();
+        if shell_path.contains("fish") {
+            vec![PathBuf::from(format!(
+                "{}/.local/share/fish/fish_history",
+                home_path
+            ))]
+        } else if shell_path.contains("zsh") {
+            vec![
+                PathBuf::from(format!("{}/.zhistory", home_path)),
+                PathBuf::from(format!("{}/.zsh_history", home_path)),
+            ]
+        } else {
+            // bash and anything else falls back to the plain/timestamped bash history format
+            vec![PathBuf::from(format!("{}/.bash_history", home_path))]
+        }
     }
 
     pub fn update_query(&mut self, new_query: String) {
         self.query = new_query
     }
 
+    // Frecency weights: how much a fuzzy score is nudged by how recently (W_RECENCY) and how
+    // often (W_FREQUENCY) a command was used, so near-tied fuzzy matches break the way a human
+    // expects instead of in file order.
+    const RECENCY_HALF_LIFE_SECS: f64 = 30.0 * 24.0 * 60.0 * 60.0;
+    const W_RECENCY: f64 = 4.0;
+    const W_FREQUENCY: f64 = 1.0;
+
     pub fn get_matched_commands<'a, 'b>(
         commands: &'a Vec<Command>,
         query: &'b String,
     ) -> Vec<&'a Command> {
+        if commands.is_empty() {
+            return vec![];
+        }
+
         fn get_score<'a>(commands: &'a [Command], query: &String) -> Vec<(&'a Command, i64)> {
             let result: Vec<(&Command, i64)> = commands
                 .iter()
@@ -200,8 +370,8 @@ impl Finder {
         }
 
         const NTHREAD: usize = 8;
-        let job_chunks = commands.chunks(commands.len() / NTHREAD);
-        let mut result = thread::scope(|s| {
+        let job_chunks = commands.chunks((commands.len() / NTHREAD).max(1));
+        let result = thread::scope(|s| {
             let mut handles = vec![];
             for chunk in job_chunks {
                 handles.push(s.spawn(move |_| get_score(chunk, query)));
@@ -215,31 +385,84 @@ impl Finder {
         })
         .unwrap();
 
-        // let mut result: Vec<(&Command, i64)> = commands
-        //     .iter()
-        //     .map(|cmd| (cmd, cmd.get_match_score(&query)))
-        //     .collect();
-
-        result.sort_by_key(|k| k.1);
-        result.reverse();
-        // result.dedup_by_key(|k| &k.0.command);
-        let ranked_result: Vec<&Command> = result
-            .into_iter()
-            .map(|k| k.0)
-            .unique_by(|cmd| &cmd.command)
+        // Deduplicate identical command strings, accumulating a frequency count and keeping
+        // the most recent timestamp seen for each unique command.
+        struct Aggregate<'a> {
+            command: &'a Command,
+            score: i64,
+            frequency: u32,
+            last_seen: u32,
+        }
+
+        let mut by_command: HashMap<&str, Aggregate> = HashMap::new();
+        for (cmd, score) in result {
+            if score == Command::NO_MATCH_SCORE {
+                continue;
+            }
+            by_command
+                .entry(cmd.command.as_str())
+                .and_modify(|agg| {
+                    agg.frequency += 1;
+                    if cmd.id > agg.last_seen {
+                        agg.last_seen = cmd.id;
+                        agg.command = cmd;
+                    }
+                })
+                .or_insert(Aggregate {
+                    command: cmd,
+                    score,
+                    frequency: 1,
+                    last_seen: cmd.id,
+                });
+        }
+
+        let now_secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let mut ranked: Vec<(&Command, f64)> = by_command
+            .into_values()
+            .map(|agg| {
+                let age_secs = now_secs.saturating_sub(agg.last_seen as u64) as f64;
+                let recency = 0.5_f64.powf(age_secs / Finder::RECENCY_HALF_LIFE_SECS);
+                let frequency = (1.0 + agg.frequency as f64).ln();
+                let key = agg.score as f64
+                    + Finder::W_RECENCY * recency
+                    + Finder::W_FREQUENCY * frequency;
+                (agg.command, key)
+            })
             .collect();
-        ranked_result
+
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        ranked.into_iter().map(|(cmd, _)| cmd).collect()
     }
 
     // Terminal UI
 
-    const NUM_SUGGESTIONS: usize = 15;
-
-    pub fn render(&mut self) -> Result<(), Box<dyn Error>> {
-        let mut stdout = std::io::stdout().into_raw_mode()?;
-
-        let blank_lines: String = (0..=Finder::NUM_SUGGESTIONS).map(|_| "\n").collect();
-        let move_cursor_up = format!("{}", cursor::Up((Finder::NUM_SUGGESTIONS + 1) as u16));
+    pub const DEFAULT_NUM_SUGGESTIONS: usize = 15;
+
+    // Below this many local matches, a cheat.sh lookup is fired automatically.
+    const MIN_LOCAL_MATCHES_BEFORE_FALLBACK: usize = 3;
+
+    // Rows reserved below the suggestion list for the preview of the currently selected
+    // command (plus one row for the separator line). A preview that wraps to more rows
+    // than this is cut off after them.
+    const PREVIEW_LINES: usize = 6;
+
+    pub fn render(&mut self, config: &Config) -> Result<(), Box<dyn Error>> {
+        // The interactive list/query/preview always goes to the controlling terminal, not
+        // stdout: `rcf print` writes the chosen command to stdout for shell-widget integration
+        // (e.g. `$(rcf print)`), and that capture must not also pick up the TUI's escape codes.
+        let tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+        let mut stdout = tty.into_raw_mode()?;
+
+        // Clamp to a sane range: at least 1 row, and well under u16::MAX so the cursor-motion
+        // escape sequences below can't wrap around.
+        let limit = std::cmp::min(std::cmp::max(config.limit, 1), 1000);
+        let reserved_lines = limit + 1 + Finder::PREVIEW_LINES + 1;
+        let blank_lines: String = (0..=reserved_lines).map(|_| "\n").collect();
+        let move_cursor_up = format!("{}", cursor::Up((reserved_lines + 1) as u16));
         write!(stdout, "{}{}{}", blank_lines, move_cursor_up, cursor::Save)?;
         stdout.flush()?;
         // TODO: well, clone isn't good...
@@ -247,12 +470,33 @@ impl Finder {
 
         let mut selecting_cmd = 0usize;
 
-        let mut truncated_matches = Finder::get_truncated_matches(&commands, &self.query);
+        let (mut truncated_matches, mut total_matches) =
+            Finder::get_truncated_matches(&commands, &self.query, limit);
+
+        let (remote_tx, remote_rx) = mpsc::channel::<(u64, Vec<Command>)>();
+        let mut remote_commands: Vec<Command> = vec![];
+        // Bumped every time a new fetch is spawned; replies tagged with an older generation
+        // (a response for a query the user has since moved past) are dropped on arrival.
+        let mut remote_generation: u64 = 0;
 
         let mut stdin = termion::async_stdin().keys();
         loop {
+            // Remote results land on a background thread's own schedule, not the user's
+            // keystrokes, so track whether this iteration actually changed what's on screen
+            // and redraw below regardless of whether a key was pressed.
+            let mut redraw = false;
+            while let Ok((generation, fetched)) = remote_rx.try_recv() {
+                if generation == remote_generation {
+                    remote_commands = fetched;
+                    redraw = true;
+                }
+            }
+
+            let display_len = std::cmp::min(truncated_matches.len() + remote_commands.len(), limit);
+
             let key = stdin.next();
             if let Some(Ok(c)) = key {
+                redraw = true;
                 match c {
                     // TODO: Handle Key::Up Key::Down https://gitlab.redox-os.org/redox-os/termion/-/issues/168
                     Key::Ctrl('p') | Key::Up => {
@@ -260,19 +504,58 @@ impl Finder {
                     }
                     Key::Ctrl('n') | Key::Down => {
                         selecting_cmd =
-                            std::cmp::min(selecting_cmd + 1, Finder::NUM_SUGGESTIONS - 1);
+                            std::cmp::min(selecting_cmd + 1, display_len.saturating_sub(1));
                     }
                     Key::Ctrl('c') => {
                         break;
                     }
                     Key::Char('\n') => {
-                        Finder::copy_command_to_clipboard(&truncated_matches, selecting_cmd)?;
-                        Finder::output_command_to_file(&truncated_matches, selecting_cmd)?;
+                        let display_matches =
+                            Finder::merge_with_remote(&truncated_matches, &remote_commands, limit);
+                        if config.print_to_stdout {
+                            stdout.suspend_raw_mode()?;
+                        }
+                        Finder::copy_command_to_clipboard(&display_matches, selecting_cmd, config)?;
+                        Finder::output_command_to_file(&display_matches, selecting_cmd, config)?;
                         break;
                     }
+                    Key::Ctrl('o') => {
+                        let display_matches =
+                            Finder::merge_with_remote(&truncated_matches, &remote_commands, limit);
+                        let cmd = Finder::get_selecting_command(&display_matches, selecting_cmd);
+                        if let Some(edited) = Finder::edit_command_in_editor(&mut stdout, &cmd)? {
+                            if config.print_to_stdout {
+                                stdout.suspend_raw_mode()?;
+                            }
+                            Finder::copy_string_to_clipboard(&edited, config)?;
+                            Finder::output_string_to_file(&edited, config)?;
+                            break;
+                        }
+                    }
+                    Key::Ctrl('s') => {
+                        remote_commands.clear();
+                        remote_generation += 1;
+                        Finder::spawn_cheat_sh_fetch(
+                            self.query.clone(),
+                            remote_generation,
+                            remote_tx.clone(),
+                        );
+                    }
                     Key::Char(ch) => {
                         let new_query = format!("{}{}", self.query, ch);
-                        truncated_matches = Finder::get_truncated_matches(&commands, &new_query);
+                        let (matches, total) =
+                            Finder::get_truncated_matches(&commands, &new_query, limit);
+                        truncated_matches = matches;
+                        total_matches = total;
+                        remote_commands.clear();
+                        if total_matches < Finder::MIN_LOCAL_MATCHES_BEFORE_FALLBACK {
+                            remote_generation += 1;
+                            Finder::spawn_cheat_sh_fetch(
+                                new_query.clone(),
+                                remote_generation,
+                                remote_tx.clone(),
+                            );
+                        }
                         self.update_query(new_query)
                     }
                     Key::Backspace => {
@@ -281,12 +564,21 @@ impl Finder {
                         } else {
                             String::from("")
                         };
-                        truncated_matches = Finder::get_truncated_matches(&commands, &new_query);
+                        let (matches, total) =
+                            Finder::get_truncated_matches(&commands, &new_query, limit);
+                        truncated_matches = matches;
+                        total_matches = total;
+                        remote_commands.clear();
+                        // No fetch is spawned here, but bump the generation anyway so a
+                        // still-in-flight fetch for the pre-backspace query is dropped on arrival.
+                        remote_generation += 1;
                         self.update_query(new_query)
                     }
                     _ => {}
                 }
+            }
 
+            if redraw {
                 write!(
                     stdout,
                     "{}{}{}",
@@ -296,25 +588,125 @@ impl Finder {
                 )?;
                 write!(stdout, "{}\r\n", self.query)?;
 
-                Finder::output_matched_commands(&truncated_matches, selecting_cmd, &mut stdout)?;
+                let display_matches =
+                    Finder::merge_with_remote(&truncated_matches, &remote_commands, limit);
+                Finder::output_matched_commands(&display_matches, selecting_cmd, &mut stdout)?;
+                Finder::render_preview(display_matches.get(selecting_cmd).copied(), &mut stdout)?;
             }
         }
 
         Ok(())
     }
 
+    // Renders the newline-preserving text of `selecting` below the suggestion list, wrapped
+    // to the terminal width, so multi-line history entries stay readable. Only the first
+    // `PREVIEW_LINES` wrapped rows are shown; the rest of the text is not displayed.
+    fn render_preview(
+        selecting: Option<&Command>,
+        stdout: &mut RawTerminal<File>,
+    ) -> Result<(), Box<dyn Error>> {
+        let (n_term_cols, _) = termion::terminal_size()?;
+        write!(stdout, "{}\r\n", "-".repeat(usize::from(n_term_cols)))?;
+
+        let text = selecting.map(|cmd| cmd.command.as_str()).unwrap_or("");
+        for line in Finder::wrap_text(text, n_term_cols).iter().take(Finder::PREVIEW_LINES) {
+            write!(stdout, "{}\r\n", line)?;
+        }
+        stdout.flush()?;
+        Ok(())
+    }
+
+    fn wrap_text(text: &str, width: u16) -> Vec<String> {
+        let width = std::cmp::max(usize::from(width), 1);
+        let mut wrapped = vec![];
+        for line in text.split('\n') {
+            let line = line.trim_end_matches('\r');
+            if line.is_empty() {
+                wrapped.push(String::new());
+                continue;
+            }
+            let chars: Vec<char> = line.chars().collect();
+            for chunk in chars.chunks(width) {
+                wrapped.push(chunk.iter().collect());
+            }
+        }
+        wrapped
+    }
+
+    // Merges remote suggestions below the local matches, capped to `limit` total so the
+    // result never exceeds the rows reserved for it at startup (an unbounded cheat.sh result
+    // set would otherwise overflow the carved-out screen region and scroll the TUI).
+    fn merge_with_remote<'a>(
+        local_matches: &Vec<&'a Command>,
+        remote_commands: &'a Vec<Command>,
+        limit: usize,
+    ) -> Vec<&'a Command> {
+        let mut merged = local_matches.clone();
+        merged.extend(remote_commands.iter());
+        merged.truncate(limit);
+        merged
+    }
+
+    // Fetches https://cheat.sh/<query> in the background and sends the parsed suggestions
+    // down `tx` tagged with `generation`, so the caller (the render loop) never blocks on the
+    // network. Any failure (offline, bad status, ...) is swallowed and simply yields no remote
+    // suggestions. `generation` lets the caller tell a late reply for a since-abandoned query
+    // apart from the reply for its current one, since fetches can finish out of order.
+    fn spawn_cheat_sh_fetch(query: String, generation: u64, tx: mpsc::Sender<(u64, Vec<Command>)>) {
+        std::thread::spawn(move || {
+            let remote = Finder::fetch_cheat_sh(&query);
+            let _ = tx.send((generation, remote));
+        });
+    }
+
+    fn fetch_cheat_sh(query: &str) -> Vec<Command> {
+        if query.trim().is_empty() {
+            return vec![];
+        }
+        let url = format!("https://cheat.sh/{}", Finder::url_encode(query));
+        let body = match ureq::get(&url).call() {
+            Ok(response) => match response.into_string() {
+                Ok(body) => body,
+                Err(_) => return vec![],
+            },
+            Err(_) => return vec![],
+        };
+        body.lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| Command::new_remote(String::from(line)))
+            .collect()
+    }
+
+    fn url_encode(query: &str) -> String {
+        query
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' | '~' => c.to_string(),
+                ' ' => String::from("+"),
+                _ => format!("%{:02X}", c as u32),
+            })
+            .collect()
+    }
+
+    // Returns the top `limit` matches along with the total number of matches found, so
+    // callers can tell "a lot of local matches, just showing the top N" apart from
+    // "genuinely few local matches" (the latter is what triggers the cheat.sh fallback).
     fn get_truncated_matches<'a, 'b>(
         commands: &'a Vec<Command>,
         query: &'b String,
-    ) -> Vec<&'a Command> {
+        limit: usize,
+    ) -> (Vec<&'a Command>, usize) {
         let matches = Finder::get_matched_commands(commands, query);
+        let total = matches.len();
 
-        if matches.len() > Finder::NUM_SUGGESTIONS {
-            let (left, _) = matches.split_at(Finder::NUM_SUGGESTIONS);
+        let truncated = if total > limit {
+            let (left, _) = matches.split_at(limit);
             left.to_vec()
         } else {
             matches
-        }
+        };
+        (truncated, total)
     }
 
     fn get_selecting_command(commands: &Vec<&Command>, selecting_cmd: usize) -> String {
@@ -327,18 +719,37 @@ impl Finder {
     fn copy_command_to_clipboard(
         commands: &Vec<&Command>,
         selecting_cmd: usize,
+        config: &Config,
     ) -> Result<(), Box<dyn Error>> {
-        let mut clipboard_ctx: ClipboardContext = ClipboardProvider::new()?;
         let cmd = Finder::get_selecting_command(commands, selecting_cmd);
-        clipboard_ctx.set_contents(cmd)?;
-        Ok(())
+        Finder::copy_string_to_clipboard(&cmd, config)
     }
 
     fn output_command_to_file(
         commands: &Vec<&Command>,
         selecting_cmd: usize,
+        config: &Config,
     ) -> Result<(), Box<dyn Error>> {
         let cmd = Finder::get_selecting_command(commands, selecting_cmd);
+        Finder::output_string_to_file(&cmd, config)
+    }
+
+    fn copy_string_to_clipboard(cmd: &str, config: &Config) -> Result<(), Box<dyn Error>> {
+        // print mode's whole point is emitting exactly one line to stdout for shell-widget
+        // capture (`$(rcf print)`); clobbering the clipboard alongside that is surprising.
+        if !config.use_clipboard || config.print_to_stdout {
+            return Ok(());
+        }
+        let mut clipboard_ctx: ClipboardContext = ClipboardProvider::new()?;
+        clipboard_ctx.set_contents(String::from(cmd))?;
+        Ok(())
+    }
+
+    fn output_string_to_file(cmd: &str, config: &Config) -> Result<(), Box<dyn Error>> {
+        if config.print_to_stdout {
+            println!("{}", cmd);
+            return Ok(());
+        }
         let mut file = OpenOptions::new()
             .write(true)
             .create(true)
@@ -348,10 +759,48 @@ impl Finder {
         Ok(())
     }
 
+    // Opens `cmd` in the user's $VISUAL/$EDITOR (falling back to vi), suspending raw mode
+    // for the duration so the editor gets a normal terminal. Returns the edited text, or
+    // None if the editor exited with a non-zero status (treated as a cancel).
+    fn edit_command_in_editor(
+        stdout: &mut RawTerminal<File>,
+        cmd: &str,
+    ) -> Result<Option<String>, Box<dyn Error>> {
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| String::from("vi"));
+
+        let tmp_path = std::env::temp_dir().join(format!("rcf-edit-{}.cmd", std::process::id()));
+        {
+            let mut tmp_file = File::create(&tmp_path)?;
+            tmp_file.write_all(cmd.as_bytes())?;
+        }
+
+        stdout.suspend_raw_mode()?;
+        let status = std::process::Command::new(&editor)
+            .arg(&tmp_path)
+            .stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .status();
+        stdout.activate_raw_mode()?;
+
+        let status = status?;
+        if !status.success() {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Ok(None);
+        }
+
+        let edited = std::fs::read_to_string(&tmp_path)?;
+        let _ = std::fs::remove_file(&tmp_path);
+
+        Ok(Some(String::from(edited.trim_end_matches(['\n', '\r']))))
+    }
+
     fn output_matched_commands(
         matches: &Vec<&Command>,
         selecting_cmd: usize,
-        stdout: &mut RawTerminal<Stdout>,
+        stdout: &mut RawTerminal<File>,
     ) -> Result<(), Box<dyn Error>> {
         let (n_term_cols, _) = termion::terminal_size()?;
         for (idx, c) in matches.into_iter().enumerate() {
@@ -362,6 +811,13 @@ impl Finder {
                     termion::color::Bg(termion::color::White),
                     termion::color::Fg(termion::color::Black)
                 )?;
+            } else if c.source == CommandSource::Remote {
+                write!(
+                    stdout,
+                    "{}{}",
+                    termion::color::Bg(termion::color::Black),
+                    termion::color::Fg(termion::color::Cyan)
+                )?;
             } else {
                 write!(
                     stdout,
@@ -370,9 +826,86 @@ impl Finder {
                     termion::color::Fg(termion::color::White)
                 )?;
             };
-            write!(stdout, "{}\r\n", c.truncate_command(n_term_cols - 5))?;
+            let prefix = if c.source == CommandSource::Remote {
+                "[www] "
+            } else {
+                ""
+            };
+            write!(
+                stdout,
+                "{}{}\r\n",
+                prefix,
+                c.truncate_command(n_term_cols - 5)
+            )?;
         }
         stdout.flush()?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_zsh_extended_history() {
+        let content = ": 1600000000:0;ls -la\n: 1600000001:0;cd /tmp\n";
+        let commands = HistoryFormat::parse_zsh_extended(content);
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].id, 1600000000);
+        assert_eq!(commands[0].command, "ls -la");
+        assert_eq!(commands[1].id, 1600000001);
+        assert_eq!(commands[1].command, "cd /tmp");
+    }
+
+    #[test]
+    fn parses_bash_plain_history() {
+        let content = "ls -la\n\ncd /tmp\n";
+        let commands = HistoryFormat::parse_bash_plain(content);
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].id, 0);
+        assert_eq!(commands[0].command, "ls -la");
+        assert_eq!(commands[1].command, "cd /tmp");
+    }
+
+    #[test]
+    fn parses_bash_history_with_timestamps() {
+        let content = "#1600000000\nls -la\n#1600000001\ncd /tmp\n";
+        let commands = HistoryFormat::parse_bash_with_timestamp(content);
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].id, 1600000000);
+        assert_eq!(commands[0].command, "ls -la");
+        assert_eq!(commands[1].id, 1600000001);
+        assert_eq!(commands[1].command, "cd /tmp");
+    }
+
+    #[test]
+    fn parses_fish_history() {
+        let content = "- cmd: ls -la\n  when: 1600000000\n- cmd: cd /tmp\n  when: 1600000001\n";
+        let commands = HistoryFormat::parse_fish(content);
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].id, 1600000000);
+        assert_eq!(commands[0].command, "ls -la");
+        assert_eq!(commands[1].id, 1600000001);
+        assert_eq!(commands[1].command, "cd /tmp");
+    }
+
+    #[test]
+    fn frecency_breaks_ties_towards_more_recent_and_frequent_commands() {
+        // An empty query fuzzy-matches everything with the same score, so the ranking is
+        // entirely decided by frecency: "git status" was used twice and recently, "git
+        // stash" only once a long time ago.
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as u32;
+        let commands = vec![
+            Command::new(now - 1, String::from("git status")),
+            Command::new(now - 1, String::from("git status")),
+            Command::new(1, String::from("git stash")),
+        ];
+        let query = String::from("");
+        let ranked = Finder::get_matched_commands(&commands, &query);
+        assert_eq!(ranked[0].command, "git status");
+    }
+}
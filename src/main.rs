@@ -1,10 +1,54 @@
 use std::error::Error;
+use std::path::PathBuf;
 
-use rcf::Finder;
+use clap::{Parser, Subcommand};
+
+use rcf::{Config, Finder};
+
+#[derive(Parser, Debug)]
+#[command(name = "rcf", about = "Fuzzy-find and recall commands from your shell history")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    /// History file to read instead of the shell's default
+    #[arg(long, global = true)]
+    history: Option<PathBuf>,
+
+    /// Max number of suggestions to show
+    #[arg(long, global = true)]
+    limit: Option<usize>,
+
+    /// Don't copy the chosen command to the clipboard
+    #[arg(long, global = true)]
+    no_clipboard: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Print the chosen command to stdout instead of writing it to /tmp/rf.cmd
+    Print,
+}
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let mut finder = Finder::new_with_bash_history()?;
-    
-    finder.render()?;
+    let cli = Cli::parse();
+
+    let mut config = Config::new();
+    if let Some(history) = cli.history {
+        config.history_file = Some(history);
+    }
+    if let Some(limit) = cli.limit {
+        config.limit = limit;
+    }
+    if cli.no_clipboard {
+        config.use_clipboard = false;
+    }
+    if matches!(cli.command, Some(Commands::Print)) {
+        config.print_to_stdout = true;
+    }
+
+    let mut finder = Finder::new_with_bash_history(&config)?;
+
+    finder.render(&config)?;
     Ok(())
 }